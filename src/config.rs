@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crossterm::style::Color;
+use serde::Deserialize;
+
+#[derive(Deserialize, Default)]
+struct RawConfig {
+    colors: Option<RawColors>,
+    keys: Option<RawKeys>,
+    editor: Option<RawEditor>,
+}
+
+#[derive(Deserialize, Default)]
+struct RawColors {
+    status_fg: Option<[u8; 3]>,
+    status_bg: Option<[u8; 3]>,
+    match_highlight: Option<[u8; 3]>,
+    keyword: Option<[u8; 3]>,
+}
+
+#[derive(Deserialize, Default)]
+struct RawKeys {
+    quit: Option<char>,
+    save: Option<char>,
+    find: Option<char>,
+}
+
+#[derive(Deserialize, Default)]
+struct RawEditor {
+    tab_stop: Option<usize>,
+}
+
+/// User-tunable colors, tab width and keybindings, loaded from
+/// `~/.config/svim/config.toml`. Falls back to the built-in defaults
+/// whenever the file is absent or fails to parse.
+pub struct Config {
+    pub status_fg: Color,
+    pub status_bg: Color,
+    pub match_color: Color,
+    pub keyword_color: Color,
+    pub tab_stop: usize,
+    pub quit_key: char,
+    pub save_key: char,
+    pub find_key: char,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            status_fg: Color::Rgb { r: 63, g: 63, b: 63 },
+            status_bg: Color::Rgb { r: 239, g: 239, b: 239 },
+            match_color: Color::Rgb { r: 38, g: 139, b: 210 },
+            keyword_color: Color::Rgb { r: 181, g: 137, b: 0 },
+            tab_stop: 8,
+            quit_key: 'q',
+            save_key: 's',
+            find_key: 'f',
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let mut config = Self::default();
+        let Some(path) = Self::config_path() else {
+            return config;
+        };
+        let Ok(contents) = fs::read_to_string(path) else {
+            return config;
+        };
+        let Ok(raw) = toml::from_str::<RawConfig>(&contents) else {
+            return config;
+        };
+        config.apply(raw);
+        config
+    }
+
+    fn config_path() -> Option<PathBuf> {
+        let home = std::env::var("HOME").ok()?;
+        Some(PathBuf::from(home).join(".config/svim/config.toml"))
+    }
+
+    fn apply(&mut self, raw: RawConfig) {
+        if let Some(colors) = raw.colors {
+            if let Some([r, g, b]) = colors.status_fg {
+                self.status_fg = Color::Rgb { r, g, b };
+            }
+            if let Some([r, g, b]) = colors.status_bg {
+                self.status_bg = Color::Rgb { r, g, b };
+            }
+            if let Some([r, g, b]) = colors.match_highlight {
+                self.match_color = Color::Rgb { r, g, b };
+            }
+            if let Some([r, g, b]) = colors.keyword {
+                self.keyword_color = Color::Rgb { r, g, b };
+            }
+        }
+        if let Some(keys) = raw.keys {
+            if let Some(c) = keys.quit {
+                self.quit_key = c;
+            }
+            if let Some(c) = keys.save {
+                self.save_key = c;
+            }
+            if let Some(c) = keys.find {
+                self.find_key = c;
+            }
+        }
+        if let Some(editor) = raw.editor {
+            if let Some(tab_stop) = editor.tab_stop {
+                // A zero tab stop would panic as a remainder-by-zero in
+                // Row::grapheme_render_width, so ignore non-positive values.
+                if tab_stop > 0 {
+                    self.tab_stop = tab_stop;
+                }
+            }
+        }
+    }
+}
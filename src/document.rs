@@ -0,0 +1,408 @@
+use std::fs;
+use std::io::{Error, Write};
+
+use crate::editor::{Position, SearchDirection};
+use crate::row::Row;
+
+#[derive(Default)]
+pub struct Document {
+    rows: Vec<Row>,
+    pub file_name: Option<String>,
+    dirty: bool,
+    undo_stack: Vec<EditGroup>,
+    redo_stack: Vec<EditGroup>,
+}
+
+/// A single reversible row mutation, recorded so `undo`/`redo` can replay its
+/// inverse. `InsertChar`/`InsertNewline` are produced by `insert`;
+/// `DeleteChar`/`JoinLines` by `delete`.
+#[derive(Clone)]
+enum EditOp {
+    InsertChar { at: Position, c: char },
+    InsertNewline { at: Position },
+    DeleteChar { at: Position, text: String },
+    JoinLines { at: Position },
+}
+
+/// One or more `EditOp`s undone/redone together. Consecutive single-char
+/// inserts typed without moving the cursor are coalesced into one group so a
+/// word isn't undone letter-by-letter.
+#[derive(Clone)]
+struct EditGroup {
+    ops: Vec<EditOp>,
+    coalescible: bool,
+}
+
+impl EditGroup {
+    fn extends(&self, op: &EditOp) -> bool {
+        match (self.ops.last(), op) {
+            (
+                Some(EditOp::InsertChar { at: prev_at, .. }),
+                EditOp::InsertChar { at, .. },
+            ) => at.y == prev_at.y && at.x == prev_at.x + 1,
+            _ => false,
+        }
+    }
+}
+
+impl Document {
+    pub fn open(filename: &str) -> Result<Self, std::io::Error> {
+        let contents = fs::read_to_string(filename)?;
+        let mut rows = Vec::new();
+        for value in contents.lines() {
+            let mut row = Row::from(value);
+            row.highlight(None);
+            rows.push(row);
+        }
+        Ok(Self {
+            rows,
+            file_name: Some(filename.to_string()),
+            dirty: false,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        })
+    }
+
+    pub fn row(&self, index: usize) -> Option<&Row> {
+        self.rows.get(index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn insert(&mut self, at: &Position, c: char) {
+        if at.y > self.rows.len() {
+            return;
+        }
+        self.dirty = true;
+        if c == '\n' {
+            self.raw_split(at);
+            self.push_op(EditOp::InsertNewline { at: at.clone() }, false);
+            return;
+        }
+        self.raw_insert_char(at, c);
+        self.push_op(EditOp::InsertChar { at: at.clone(), c }, true);
+    }
+
+    pub fn delete(&mut self, at: &Position) {
+        let len = self.rows.len();
+        if at.y >= len {
+            return;
+        }
+        self.dirty = true;
+        if at.x == self.rows[at.y].len() && at.y + 1 < len {
+            self.raw_join(at.y);
+            self.push_op(EditOp::JoinLines { at: at.clone() }, false);
+        } else if let Some(text) = self.raw_delete_char(at) {
+            self.push_op(
+                EditOp::DeleteChar {
+                    at: at.clone(),
+                    text,
+                },
+                false,
+            );
+        }
+    }
+
+    /// Undoes the most recent edit group, returning the cursor position the
+    /// editor should move to, or `None` if there's nothing to undo.
+    pub fn undo(&mut self) -> Option<Position> {
+        let group = self.undo_stack.pop()?;
+        let mut cursor = None;
+        for op in group.ops.iter().rev() {
+            cursor = Some(self.undo_op(op));
+        }
+        self.redo_stack.push(group);
+        self.dirty = true;
+        cursor
+    }
+
+    /// Re-applies the most recently undone edit group, returning the cursor
+    /// position the editor should move to, or `None` if there's nothing to redo.
+    pub fn redo(&mut self) -> Option<Position> {
+        let group = self.redo_stack.pop()?;
+        let mut cursor = None;
+        for op in &group.ops {
+            cursor = Some(self.redo_op(op));
+        }
+        self.undo_stack.push(group);
+        self.dirty = true;
+        cursor
+    }
+
+    fn undo_op(&mut self, op: &EditOp) -> Position {
+        match op {
+            EditOp::InsertChar { at, .. } => {
+                self.raw_delete_char(at);
+                at.clone()
+            }
+            EditOp::InsertNewline { at } => {
+                self.raw_join(at.y);
+                at.clone()
+            }
+            EditOp::DeleteChar { at, text } => {
+                self.raw_insert_str(at, text);
+                Position {
+                    x: at.x + 1,
+                    y: at.y,
+                }
+            }
+            EditOp::JoinLines { at } => {
+                self.raw_split(at);
+                at.clone()
+            }
+        }
+    }
+
+    fn redo_op(&mut self, op: &EditOp) -> Position {
+        match op {
+            EditOp::InsertChar { at, c } => {
+                self.raw_insert_char(at, *c);
+                Position {
+                    x: at.x + 1,
+                    y: at.y,
+                }
+            }
+            EditOp::InsertNewline { at } => {
+                self.raw_split(at);
+                Position { x: 0, y: at.y + 1 }
+            }
+            EditOp::DeleteChar { at, .. } => {
+                self.raw_delete_char(at);
+                at.clone()
+            }
+            EditOp::JoinLines { at } => {
+                self.raw_join(at.y);
+                at.clone()
+            }
+        }
+    }
+
+    /// Records `op` on the undo stack and clears the redo stack, coalescing it
+    /// into the previous group when `coalescible` and the two ops are
+    /// contiguous single-char inserts.
+    fn push_op(&mut self, op: EditOp, coalescible: bool) {
+        self.redo_stack.clear();
+        if coalescible {
+            if let Some(last_group) = self.undo_stack.last_mut() {
+                if last_group.coalescible && last_group.extends(&op) {
+                    last_group.ops.push(op);
+                    return;
+                }
+            }
+        }
+        self.undo_stack.push(EditGroup {
+            ops: vec![op],
+            coalescible,
+        });
+    }
+
+    fn raw_insert_char(&mut self, at: &Position, c: char) {
+        self.raw_insert_str(at, &c.to_string());
+    }
+
+    fn raw_insert_str(&mut self, at: &Position, text: &str) {
+        if at.y == self.rows.len() {
+            let mut row = Row::default();
+            row.insert_str(0, text);
+            self.rows.push(row);
+        } else {
+            self.rows[at.y].insert_str(at.x, text);
+        }
+    }
+
+    fn raw_delete_char(&mut self, at: &Position) -> Option<String> {
+        self.rows[at.y].remove_grapheme(at.x)
+    }
+
+    fn raw_split(&mut self, at: &Position) {
+        if at.y > self.rows.len() {
+            return;
+        }
+        if at.y == self.rows.len() {
+            self.rows.push(Row::default());
+            return;
+        }
+        let new_row = self.rows[at.y].split(at.x);
+        self.rows.insert(at.y + 1, new_row);
+    }
+
+    fn raw_join(&mut self, y: usize) {
+        let next_row = self.rows.remove(y + 1);
+        self.rows[y].append(&next_row);
+    }
+
+    pub fn highlight(&mut self, word: Option<&str>) {
+        for row in &mut self.rows {
+            row.highlight(word);
+        }
+    }
+
+    pub fn save(&mut self) -> Result<(), Error> {
+        if let Some(file_name) = &self.file_name {
+            let mut file = fs::File::create(file_name)?;
+            for row in &self.rows {
+                file.write_all(row.as_bytes())?;
+                file.write_all(b"\n")?;
+            }
+            self.dirty = false;
+        }
+        Ok(())
+    }
+
+    /// Flushes a dirty document to a `.swp` sibling of its file (or
+    /// `.svim.swp` for an unnamed buffer), without touching the real file or
+    /// clearing the dirty flag. Meant to be called periodically in the
+    /// background so in-progress work survives a crash between saves.
+    pub fn autosave(&mut self) -> Result<(), Error> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let swap_name = match &self.file_name {
+            Some(name) => format!("{}.swp", name),
+            None => ".svim.swp".to_string(),
+        };
+        let mut file = fs::File::create(swap_name)?;
+        for row in &self.rows {
+            file.write_all(row.as_bytes())?;
+            file.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    pub fn find(&self, query: &str, after: &Position, direction: SearchDirection) -> Option<Position> {
+        if after.y >= self.rows.len() {
+            return None;
+        }
+        let mut position = Position {
+            x: after.x,
+            y: after.y,
+        };
+
+        let start = if direction == SearchDirection::Forward {
+            after.y
+        } else {
+            0
+        };
+        let end = if direction == SearchDirection::Forward {
+            self.rows.len()
+        } else {
+            after.y.saturating_add(1)
+        };
+
+        for _ in start..end {
+            if let Some(row) = self.rows.get(position.y) {
+                if let Some(x) = row.find(query, position.x, direction) {
+                    position.x = x;
+                    return Some(position);
+                }
+                if direction == SearchDirection::Forward {
+                    position.y = position.y.saturating_add(1);
+                    position.x = 0;
+                } else {
+                    position.y = position.y.saturating_sub(1);
+                    position.x = self.rows[position.y].len();
+                }
+            } else {
+                return None;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn doc_from(lines: &[&str]) -> Document {
+        let mut document = Document::default();
+        for line in lines {
+            let mut row = Row::from(*line);
+            row.highlight(None);
+            document.rows.push(row);
+        }
+        document
+    }
+
+    /// Renders a row's plain text, stripping the trailing reset escape that
+    /// `Row::render` always appends so assertions can compare plain strings.
+    fn rendered(document: &Document, y: usize) -> String {
+        let text = document
+            .row(y)
+            .unwrap()
+            .render(0, 100, &Config::default());
+        text.trim_end_matches("\u{1b}[0m").to_string()
+    }
+
+    #[test]
+    fn find_forward_then_continuing_advances_to_next_match() {
+        let document = doc_from(&["foo bar foo foo"]);
+        let start = Position { x: 0, y: 0 };
+        let first = document
+            .find("foo", &start, SearchDirection::Forward)
+            .unwrap();
+        assert_eq!(first, Position { x: 0, y: 0 });
+
+        // Continuing forward (as Editor::search does, by stepping past the
+        // current match before re-searching) must land on the *next*
+        // occurrence instead of re-finding the one under the cursor.
+        let continue_from = Position {
+            x: first.x + 1,
+            y: first.y,
+        };
+        let second = document
+            .find("foo", &continue_from, SearchDirection::Forward)
+            .unwrap();
+        assert_eq!(second, Position { x: 8, y: 0 });
+
+        let continue_from = Position {
+            x: second.x + 1,
+            y: second.y,
+        };
+        let third = document
+            .find("foo", &continue_from, SearchDirection::Forward)
+            .unwrap();
+        assert_eq!(third, Position { x: 12, y: 0 });
+    }
+
+    #[test]
+    fn undo_reverts_insert_and_redo_reapplies_it() {
+        let mut document = doc_from(&["ab"]);
+        let at = Position { x: 1, y: 0 };
+        document.insert(&at, 'x');
+        assert_eq!(rendered(&document, 0), "axb");
+
+        let undo_posi = document.undo().unwrap();
+        assert_eq!(undo_posi, at);
+        assert_eq!(rendered(&document, 0), "ab");
+
+        let redo_posi = document.redo().unwrap();
+        assert_eq!(redo_posi, Position { x: 2, y: 0 });
+        assert_eq!(rendered(&document, 0), "axb");
+    }
+
+    #[test]
+    fn consecutive_single_char_inserts_coalesce_into_one_undo_group() {
+        let mut document = doc_from(&[""]);
+        document.insert(&Position { x: 0, y: 0 }, 'a');
+        document.insert(&Position { x: 1, y: 0 }, 'b');
+        document.insert(&Position { x: 2, y: 0 }, 'c');
+        assert_eq!(rendered(&document, 0), "abc");
+
+        let posi = document.undo().unwrap();
+        assert_eq!(posi, Position { x: 0, y: 0 });
+        assert_eq!(rendered(&document, 0), "");
+        assert!(document.undo().is_none());
+    }
+}
@@ -1,33 +1,45 @@
-use std::io::{self, stdout, Error};
+use std::io::Error;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
 use std::time::{Duration, Instant};
-use termion::event::Key;
-use termion::{color, input::TermRead, raw::IntoRawMode};
 
-use crate::document::{self, Document};
-use crate::row::Row;
-use crate::terminal::{self, Terminal};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time;
+
+use crate::config::Config;
+use crate::document::Document;
+use crate::terminal::{Key, Terminal};
 
-const STATUS_FG_COLOR: color::Rgb = color::Rgb(63, 63, 63);
-const STATUS_BG_COLOR: color::Rgb = color::Rgb(239, 239, 239);
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const QUIT_TIMES: u8 = 3;
+const STATUS_TICK: Duration = Duration::from_secs(1);
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
 
 pub struct Editor {
     should_quit: bool,
     terminal: Terminal,
     cursor_posi: Position,
     offset: Position,
-    document: Document,
+    document: Arc<Mutex<Document>>,
     status_msg: StatusMessage,
     quit_times: u8,
+    highlighted_word: Option<String>,
+    config: Config,
+    key_rx: UnboundedReceiver<Key>,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq, Debug)]
 pub struct Position {
     pub x: usize,
     pub y: usize,
 }
 
+#[derive(PartialEq, Clone, Copy)]
+pub enum SearchDirection {
+    Forward,
+    Backward,
+}
+
 struct StatusMessage {
     text: String,
     time: Instant,
@@ -44,7 +56,21 @@ impl StatusMessage {
 
 impl Editor {
     pub fn run(&mut self) {
-        let _stdout = stdout().into_raw_mode().unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("Failed to start async runtime.");
+        runtime.block_on(self.run_async());
+    }
+
+    /// Drives the editor without ever blocking on a keypress: a background
+    /// thread feeds decoded keys into `key_rx`, and `select!` wakes this loop
+    /// either when a key arrives or the status-message tick fires, so the
+    /// autosave task (spawned alongside) keeps running even while the user is
+    /// idle.
+    async fn run_async(&mut self) {
+        Self::spawn_autosave(Arc::clone(&self.document));
+        let mut status_tick = time::interval(STATUS_TICK);
 
         loop {
             if let Err(error) = self.refresh_screen() {
@@ -53,38 +79,78 @@ impl Editor {
             if self.should_quit {
                 break;
             }
-            if let Err(error) = self.process_keypress() {
-                die(&error);
+            tokio::select! {
+                key = self.key_rx.recv() => {
+                    match key {
+                        Some(key) => {
+                            if let Err(error) = self.process_keypress(key).await {
+                                die(&error);
+                            }
+                        }
+                        None => self.should_quit = true,
+                    }
+                }
+                _ = status_tick.tick() => {}
             }
         }
     }
 
-    fn refresh_screen(&self) -> Result<(), std::io::Error> {
-        Terminal::clear_screen();
-        Terminal::cursor_position(&Position::default());
+    /// Spawns the blocking key-reader thread and returns the receiving end of
+    /// the channel it feeds.
+    fn spawn_key_reader() -> UnboundedReceiver<Key> {
+        let (tx, rx): (UnboundedSender<Key>, UnboundedReceiver<Key>) = mpsc::unbounded_channel();
+        thread::spawn(move || {
+            while let Ok(key) = Terminal::read_key() {
+                if tx.send(key).is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+
+    /// Spawns the background autosave task: every `AUTOSAVE_INTERVAL` it
+    /// flushes a dirty document to its `.swp` file, independent of keypresses.
+    fn spawn_autosave(document: Arc<Mutex<Document>>) {
+        tokio::spawn(async move {
+            let mut ticker = time::interval(AUTOSAVE_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let _ = document.lock().expect("document lock poisoned").autosave();
+            }
+        });
+    }
+
+    fn doc(&self) -> MutexGuard<'_, Document> {
+        self.document.lock().expect("document lock poisoned")
+    }
+
+    fn refresh_screen(&mut self) -> Result<(), std::io::Error> {
+        self.terminal.cursor_hide();
+        self.terminal.clear_screen();
+        self.terminal.cursor_position(&Position::default());
         if self.should_quit {
-            Terminal::clear_screen();
-            println!("Exit.")
+            self.terminal.clear_screen();
+            self.terminal.print_line("Exit.");
         } else {
             self.draw_rows();
             self.draw_status_bar();
             self.draw_message_bar();
-            Terminal::cursor_position(&Position {
-                x: self.cursor_posi.x.saturating_sub(self.offset.x),
-                y: self.cursor_posi.y.saturating_sub(self.offset.y),
-            })
+            let x = self.render_x().saturating_sub(self.offset.x);
+            let y = self.cursor_posi.y.saturating_sub(self.offset.y);
+            self.terminal.cursor_position(&Position { x, y });
         }
-        Terminal::cursor_show();
-        Terminal::flush()
+        self.terminal.cursor_show();
+        self.terminal.flush()
     }
 
-    fn process_keypress(&mut self) -> Result<(), std::io::Error> {
-        let press_key = Terminal::read_key()?;
+    async fn process_keypress(&mut self, press_key: Key) -> Result<(), std::io::Error> {
         match press_key {
-            Key::Ctrl('q') => {
-                if self.quit_times > 0 && self.document.is_dirty() {
+            Key::Ctrl(c) if c == self.config.quit_key => {
+                if self.quit_times > 0 && self.doc().is_dirty() {
                     self.status_msg = StatusMessage::from(format!(
-                        "WARNING! File has unsaved changes. Press Ctrl-Q {} more times to quit.",
+                        "WARNING! File has unsaved changes. Press Ctrl-{} {} more times to quit.",
+                        self.config.quit_key.to_ascii_uppercase(),
                         self.quit_times
                     ));
                     self.quit_times -= 1;
@@ -92,23 +158,26 @@ impl Editor {
                 }
                 self.should_quit = true;
             }
-            Key::Ctrl('s') => self.save(),
+            Key::Ctrl(c) if c == self.config.save_key => self.save().await,
+            Key::Ctrl(c) if c == self.config.find_key => self.search().await,
+            Key::Ctrl('z') => self.undo(),
+            Key::Ctrl('y') => self.redo(),
             Key::Up | Key::Down | Key::Left | Key::Right | Key::PageDown | Key::PageUp => {
                 self.move_cursor(press_key)
             }
-            Key::Backspace => {
-                if self.cursor_posi.x > 0 || self.cursor_posi.y > 0 {
-                    self.move_cursor(Key::Left);
-                    self.document.delete(&self.cursor_posi);
-                }
+            Key::Backspace if self.cursor_posi.x > 0 || self.cursor_posi.y > 0 => {
+                self.move_cursor(Key::Left);
+                let at = self.cursor_posi.clone();
+                self.doc().delete(&at);
             }
             Key::Char(c) => {
-                println!("char: {:?}", c);
-                self.document.insert(&self.cursor_posi, c);
+                let at = self.cursor_posi.clone();
+                self.doc().insert(&at, c);
                 self.move_cursor(Key::Right);
             }
             _ => (),
         }
+        self.doc().highlight(self.highlighted_word.as_deref());
         self.scroll();
         if self.quit_times < QUIT_TIMES {
             self.quit_times = QUIT_TIMES;
@@ -120,11 +189,9 @@ impl Editor {
 
     fn move_cursor(&mut self, key: Key) {
         let Position { mut y, mut x } = self.cursor_posi;
-        let size = self.terminal.size();
-        let height = self.document.len();
+        let height = self.doc().len();
         let terminal_height = self.terminal.size().height as usize;
-        // let width = size.width.saturating_sub(1) as usize;
-        let mut width = if let Some(row) = self.document.row(y) {
+        let mut width = if let Some(row) = self.doc().row(y) {
             row.len()
         } else {
             0
@@ -132,17 +199,13 @@ impl Editor {
 
         match key {
             Key::Up => y = y.saturating_sub(1),
-            Key::Down => {
-                if y < height {
-                    y = y.saturating_add(1);
-                }
-            }
+            Key::Down if y < height => y = y.saturating_add(1),
             Key::Left => {
                 if x > 0 {
                     x -= 1;
                 } else if y > 0 {
                     y -= 1;
-                    if let Some(row) = self.document.row(y) {
+                    if let Some(row) = self.doc().row(y) {
                         x = row.len();
                     } else {
                         x = 0;
@@ -176,7 +239,7 @@ impl Editor {
             _ => (),
         }
 
-        width = if let Some(row) = self.document.row(y) {
+        width = if let Some(row) = self.doc().row(y) {
             row.len()
         } else {
             0
@@ -188,32 +251,34 @@ impl Editor {
         self.cursor_posi = Position { x, y }
     }
 
-    fn draw_row(&self, row: &Row) {
+    fn draw_row(&mut self, at: usize) {
         let width = self.terminal.size().width as usize;
         let start = self.offset.x;
         let end = self.offset.x.saturating_add(width);
-        let row = row.render(start, end);
-        println!("{}\r", row);
+        let rendered = self
+            .doc()
+            .row(at)
+            .map_or_else(String::new, |row| row.render(start, end, &self.config));
+        self.terminal.print_line(&rendered);
     }
 
-    fn draw_rows(&self) {
+    fn draw_rows(&mut self) {
         let height = self.terminal.size().height;
         for terminal_row in 0..height {
-            Terminal::clear_current_line();
-            if let Some(row) = self
-                .document
-                .row(self.offset.y.saturating_add(terminal_row as usize))
-            {
-                self.draw_row(row);
-            } else if self.document.is_empty() && terminal_row == height / 3 {
+            self.terminal.clear_current_line();
+            let at = self.offset.y.saturating_add(terminal_row as usize);
+            let has_row = self.doc().row(at).is_some();
+            if has_row {
+                self.draw_row(at);
+            } else if self.doc().is_empty() && terminal_row == height / 3 {
                 self.draw_welcom_msg();
             } else {
-                print!("~\r");
+                self.terminal.print_line("~");
             }
         }
     }
 
-    fn draw_welcom_msg(&self) {
+    fn draw_welcom_msg(&mut self) {
         let mut welcom_msg = format!("svim editor -- version {}", VERSION);
         let width = self.terminal.size().width as usize;
         let len = welcom_msg.len();
@@ -222,15 +287,23 @@ impl Editor {
 
         welcom_msg = format!("~{}{}", spaces, welcom_msg);
         welcom_msg.truncate(width);
-        println!("{}\r", welcom_msg);
+        self.terminal.print_line(&welcom_msg);
+    }
+
+    /// The cursor's on-screen column, accounting for tab stops and wide graphemes.
+    fn render_x(&self) -> usize {
+        self.doc()
+            .row(self.cursor_posi.y)
+            .map_or(0, |row| row.width_until(self.cursor_posi.x, &self.config))
     }
 
     fn scroll(&mut self) {
-        let Position { x, y } = self.cursor_posi;
+        let y = self.cursor_posi.y;
+        let x = self.render_x();
         let width = self.terminal.size().width as usize;
         let height = self.terminal.size().height as usize;
 
-        let mut offset = &mut self.offset;
+        let offset = &mut self.offset;
         if y < offset.y {
             offset.y = y;
         } else if y >= offset.y.saturating_add(height) {
@@ -245,9 +318,14 @@ impl Editor {
 
     pub fn default() -> Self {
         let args: Vec<String> = std::env::args().collect();
-        let mut initial_status = String::from("HELP: Ctrl-Q = quit | HELP: Ctrl-S = save");
+        let config = Config::load();
+        let mut initial_status = format!(
+            "HELP: Ctrl-{} = quit | HELP: Ctrl-{} = save",
+            config.quit_key.to_ascii_uppercase(),
+            config.save_key.to_ascii_uppercase()
+        );
         let document = if let Some(file_name) = args.get(1) {
-            let doc = Document::open(&file_name);
+            let doc = Document::open(file_name);
             if let Ok(doc) = doc {
                 doc
             } else {
@@ -262,118 +340,170 @@ impl Editor {
             terminal: Terminal::default().expect("Failed to init terminal."),
             cursor_posi: Position::default(),
             offset: Position::default(),
-            document,
+            document: Arc::new(Mutex::new(document)),
             status_msg: StatusMessage::from(initial_status),
             quit_times: QUIT_TIMES,
+            highlighted_word: None,
+            config,
+            key_rx: Self::spawn_key_reader(),
         }
     }
 
-    fn draw_status_bar(&self) {
-        let space = " ".repeat(self.terminal.size().width as usize);
-        Terminal::set_bg_color(STATUS_BG_COLOR);
-
-        let mut status;
+    fn draw_status_bar(&mut self) {
         let width = self.terminal.size().width as usize;
-        let modified_indicator = if self.document.is_dirty() {
-            " (modified)"
-        } else {
-            ""
-        };
+        let doc = self.doc();
+        let modified_indicator = if doc.is_dirty() { " (modified)" } else { "" };
         let mut file_name = "[NoName]".to_string();
-        if let Some(name) = &self.document.file_name {
+        if let Some(name) = &doc.file_name {
             file_name = name.clone();
             file_name.truncate(20);
         }
-        status = format!(
-            "{} - {} lines {}",
-            file_name,
-            self.document.len(),
-            modified_indicator
-        );
+        let mut status = format!("{} - {} lines {}", file_name, doc.len(), modified_indicator);
         if width > status.len() {
             status.push_str(&" ".repeat(width - status.len()));
         }
-        let line_indicator = format!(
-            "{}/{}",
-            self.cursor_posi.y.saturating_add(1),
-            self.document.len()
-        );
+        let line_indicator = format!("{}/{}", self.cursor_posi.y.saturating_add(1), doc.len());
+        drop(doc);
         let len = status.len() + line_indicator.len();
         status.push_str(&" ".repeat(width.saturating_sub(len)));
         status = format!("{}{}", status, line_indicator);
         status.truncate(width);
-        Terminal::set_bg_color(STATUS_BG_COLOR);
-        Terminal::set_fg_color(STATUS_FG_COLOR);
-        println!("{}\r", status);
-        Terminal::reset_fg_color();
-        Terminal::reset_bg_color();
+        self.terminal.set_bg_color(self.config.status_bg);
+        self.terminal.set_fg_color(self.config.status_fg);
+        self.terminal.print_line(&status);
+        self.terminal.reset_fg_color();
+        self.terminal.reset_bg_color();
     }
 
-    fn draw_message_bar(&self) {
-        Terminal::clear_current_line();
+    fn draw_message_bar(&mut self) {
+        self.terminal.clear_current_line();
         let msg = &self.status_msg;
         if Instant::now() - msg.time < Duration::new(5, 0) {
             let mut text = msg.text.clone();
             text.truncate(self.terminal.size().width as usize);
-            print!("{}", text);
+            self.terminal.print(&text);
         }
     }
 
-    fn prompt(&mut self, tips: &str) -> Result<Option<String>, Error> {
+    async fn prompt<C>(&mut self, tips: &str, mut callback: C) -> Result<Option<String>, Error>
+    where
+        C: FnMut(&mut Self, Key, &str),
+    {
         let mut result = String::new();
         loop {
             self.status_msg = StatusMessage::from(format!("{}{}", tips, result));
             self.refresh_screen()?;
-            match crate::editor::read_key()? {
+            let Some(key) = self.key_rx.recv().await else {
+                break;
+            };
+            match key {
                 Key::Backspace => result.truncate(result.len().saturating_sub(1)),
                 Key::Char('\n') => {
                     break;
                 }
-                Key::Char(c) => {
-                    if !c.is_control() {
-                        result.push(c);
-                    }
-                }
+                Key::Char(c) if !c.is_control() => result.push(c),
                 Key::Esc => {
                     result.truncate(0);
+                    callback(self, key, &result);
                     break;
                 }
                 _ => {}
             }
-            self.status_msg = StatusMessage::from(String::new());
-            if result.is_empty() {
-                return Ok(None);
-            }
+            callback(self, key, &result);
+        }
+        self.status_msg = StatusMessage::from(String::new());
+        if result.is_empty() {
+            return Ok(None);
         }
         Ok(Some(result))
     }
 
-    fn save(&mut self) {
-        if self.document.file_name.is_none() {
-            let new_name = self.prompt("Save as: ").unwrap_or(None);
+    async fn save(&mut self) {
+        let needs_name = self.doc().file_name.is_none();
+        if needs_name {
+            let new_name = self.prompt("Save as: ", |_, _, _| {}).await.unwrap_or(None);
             if new_name.is_none() {
                 self.status_msg = StatusMessage::from("Save aborted.".to_string());
                 return;
             }
-            self.document.file_name = new_name;
+            self.doc().file_name = new_name;
         }
-        if self.document.save().is_ok() {
+        let saved = self.doc().save().is_ok();
+        if saved {
             self.status_msg = StatusMessage::from("File saved successfully.".to_string());
         } else {
             self.status_msg = StatusMessage::from("File save failed.".to_string());
         }
     }
-}
 
-fn read_key() -> Result<Key, std::io::Error> {
-    loop {
-        if let Some(key) = io::stdin().lock().keys().next() {
-            return key;
+    fn undo(&mut self) {
+        let posi = self.doc().undo();
+        if let Some(posi) = posi {
+            self.cursor_posi = posi;
+            self.scroll();
+        }
+    }
+
+    fn redo(&mut self) {
+        let posi = self.doc().redo();
+        if let Some(posi) = posi {
+            self.cursor_posi = posi;
+            self.scroll();
+        }
+    }
+
+    async fn search(&mut self) {
+        let old_posi = self.cursor_posi.clone();
+        let old_offset = self.offset.clone();
+        let mut direction = SearchDirection::Forward;
+
+        let query = self
+            .prompt(
+                "Search (Esc to cancel, Arrows to navigate): ",
+                |editor, key, query| {
+                    let advancing = matches!(key, Key::Right | Key::Down | Key::Left | Key::Up);
+                    match key {
+                        Key::Right | Key::Down => direction = SearchDirection::Forward,
+                        Key::Left | Key::Up => direction = SearchDirection::Backward,
+                        _ => direction = SearchDirection::Forward,
+                    }
+                    // When the user is asking to jump to the *next* match
+                    // (rather than just having typed another query
+                    // character), step past the match we're already sitting
+                    // on so we don't find it again and get stuck.
+                    let search_from = if advancing {
+                        match direction {
+                            SearchDirection::Forward => Position {
+                                x: editor.cursor_posi.x.saturating_add(1),
+                                y: editor.cursor_posi.y,
+                            },
+                            SearchDirection::Backward => editor.cursor_posi.clone(),
+                        }
+                    } else {
+                        editor.cursor_posi.clone()
+                    };
+                    let found = editor.doc().find(query, &search_from, direction);
+                    if let Some(posi) = found {
+                        editor.cursor_posi = posi;
+                        editor.scroll();
+                    }
+                    editor.highlighted_word = Some(query.to_string());
+                    editor.doc().highlight(Some(query));
+                },
+            )
+            .await
+            .unwrap_or(None);
+
+        if query.is_none() {
+            self.cursor_posi = old_posi;
+            self.offset = old_offset;
+            self.scroll();
         }
+        self.highlighted_word = None;
+        self.doc().highlight(None);
     }
 }
 
-fn die(e: &io::Error) {
-    print!("{}", termion::clear::All);
+fn die(e: &std::io::Error) {
     panic!("{}", e);
 }
@@ -0,0 +1,35 @@
+use crossterm::style::Color;
+
+use crate::config::Config;
+
+const KEYWORDS: &[&str] = &[
+    "fn", "let", "mut", "if", "else", "match", "for", "while", "loop", "return", "struct", "enum",
+    "impl", "pub", "use", "mod", "self", "Self", "true", "false",
+];
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum HighlightType {
+    Normal,
+    Number,
+    String,
+    Comment,
+    Keyword,
+    Match,
+}
+
+impl HighlightType {
+    pub fn to_color(self, config: &Config) -> Color {
+        match self {
+            HighlightType::Number => Color::Rgb { r: 220, g: 163, b: 163 },
+            HighlightType::String => Color::Rgb { r: 211, g: 54, b: 130 },
+            HighlightType::Comment => Color::Rgb { r: 133, g: 153, b: 0 },
+            HighlightType::Keyword => config.keyword_color,
+            HighlightType::Match => config.match_color,
+            HighlightType::Normal => Color::Rgb { r: 255, g: 255, b: 255 },
+        }
+    }
+
+    pub fn is_keyword(word: &str) -> bool {
+        KEYWORDS.contains(&word)
+    }
+}
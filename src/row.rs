@@ -0,0 +1,367 @@
+use std::cmp;
+use crossterm::style::{ResetColor, SetForegroundColor};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::config::Config;
+use crate::editor::SearchDirection;
+use crate::highlighting::HighlightType;
+
+#[derive(Default)]
+pub struct Row {
+    string: String,
+    highlighting: Vec<HighlightType>,
+    len: usize,
+}
+
+impl From<&str> for Row {
+    fn from(slice: &str) -> Self {
+        let mut row = Self {
+            string: String::from(slice),
+            highlighting: Vec::new(),
+            len: 0,
+        };
+        row.update_len();
+        row
+    }
+}
+
+impl Row {
+    /// Renders the visual columns in `[start, end)`. `start`/`end` are on-screen
+    /// column positions, not grapheme indices, so tabs and wide graphemes land
+    /// at the right spot after horizontal scrolling.
+    pub fn render(&self, start: usize, end: usize, config: &Config) -> String {
+        let mut result = String::new();
+        let mut current_highlighting = &HighlightType::Normal;
+        let mut render_x = 0;
+        for (index, grapheme) in self.string[..].graphemes(true).enumerate() {
+            if render_x >= end {
+                break;
+            }
+            let grapheme_start = render_x;
+            let grapheme_width = Self::grapheme_render_width(grapheme, render_x, config.tab_stop);
+            render_x += grapheme_width;
+            if grapheme_start < start {
+                continue;
+            }
+
+            let highlighting_type = self
+                .highlighting
+                .get(index)
+                .unwrap_or(&HighlightType::Normal);
+            if highlighting_type != current_highlighting {
+                current_highlighting = highlighting_type;
+                result.push_str(&format!(
+                    "{}",
+                    SetForegroundColor(highlighting_type.to_color(config))
+                ));
+            }
+            if grapheme == "\t" {
+                result.push_str(&" ".repeat(grapheme_width));
+            } else {
+                result.push_str(grapheme);
+            }
+        }
+        result.push_str(&format!("{}", ResetColor));
+        result
+    }
+
+    /// Visual column width a grapheme occupies when rendered starting at `render_x`.
+    fn grapheme_render_width(grapheme: &str, render_x: usize, tab_stop: usize) -> usize {
+        if grapheme == "\t" {
+            tab_stop - (render_x % tab_stop)
+        } else {
+            cmp::max(grapheme.width(), 1)
+        }
+    }
+
+    /// On-screen column of the grapheme at `grapheme_index`, accounting for
+    /// tab stops and East-Asian wide characters.
+    pub fn width_until(&self, grapheme_index: usize, config: &Config) -> usize {
+        let mut render_x = 0;
+        for grapheme in self.string[..].graphemes(true).take(grapheme_index) {
+            render_x += Self::grapheme_render_width(grapheme, render_x, config.tab_stop);
+        }
+        render_x
+    }
+
+    pub fn highlight(&mut self, word: Option<&str>) {
+        let graphemes: Vec<&str> = self.string[..].graphemes(true).collect();
+        let mut highlighting = Vec::with_capacity(graphemes.len());
+        let mut index = 0;
+
+        let mut in_string: Option<&str> = None;
+        while index < graphemes.len() {
+            let grapheme = graphemes[index];
+
+            if let Some(quote) = in_string {
+                highlighting.push(HighlightType::String);
+                if grapheme == quote {
+                    in_string = None;
+                }
+                index += 1;
+                continue;
+            }
+
+            if grapheme == "\"" || grapheme == "'" {
+                in_string = Some(grapheme);
+                highlighting.push(HighlightType::String);
+                index += 1;
+                continue;
+            }
+
+            if grapheme == "/" && graphemes.get(index + 1) == Some(&"/") {
+                for _ in index..graphemes.len() {
+                    highlighting.push(HighlightType::Comment);
+                }
+                break;
+            }
+
+            if grapheme.chars().all(|c| c.is_ascii_digit()) {
+                while index < graphemes.len()
+                    && (graphemes[index].chars().all(|c| c.is_ascii_digit())
+                        || graphemes[index] == ".")
+                {
+                    highlighting.push(HighlightType::Number);
+                    index += 1;
+                }
+                continue;
+            }
+
+            if grapheme.chars().all(|c| c.is_alphabetic() || c == '_') {
+                let start = index;
+                while index < graphemes.len()
+                    && graphemes[index]
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_')
+                {
+                    index += 1;
+                }
+                let word_str = graphemes[start..index].concat();
+                let highlight_type = if HighlightType::is_keyword(&word_str) {
+                    HighlightType::Keyword
+                } else {
+                    HighlightType::Normal
+                };
+                for _ in start..index {
+                    highlighting.push(highlight_type);
+                }
+                continue;
+            }
+
+            highlighting.push(HighlightType::Normal);
+            index += 1;
+        }
+
+        if let Some(word) = word {
+            if !word.is_empty() {
+                let match_len = word.graphemes(true).count();
+                let mut search_index = 0;
+                while let Some(match_index) =
+                    self.find(word, search_index, SearchDirection::Forward)
+                {
+                    for offset in 0..match_len {
+                        if let Some(h) = highlighting.get_mut(match_index + offset) {
+                            *h = HighlightType::Match;
+                        }
+                    }
+                    search_index = match_index + match_len;
+                    if search_index > self.len {
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.highlighting = highlighting;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn insert_str(&mut self, at: usize, text: &str) {
+        if at >= self.len() {
+            self.string.push_str(text);
+        } else {
+            let mut result: String = self.string[..].graphemes(true).take(at).collect();
+            let remainder: String = self.string[..].graphemes(true).skip(at).collect();
+            result.push_str(text);
+            result.push_str(&remainder);
+            self.string = result;
+        }
+        self.update_len();
+    }
+
+    /// Deletes the grapheme at `at` and returns the text that was removed, so
+    /// callers (the undo journal) can reinsert it verbatim later.
+    pub fn remove_grapheme(&mut self, at: usize) -> Option<String> {
+        if at >= self.len() {
+            return None;
+        }
+        let removed = self.string[..].graphemes(true).nth(at).map(String::from);
+        let mut result: String = self.string[..].graphemes(true).take(at).collect();
+        let remainder: String = self.string[..].graphemes(true).skip(at + 1).collect();
+        result.push_str(&remainder);
+        self.string = result;
+        self.update_len();
+        removed
+    }
+
+    pub fn append(&mut self, new: &Self) {
+        self.string = format!("{}{}", self.string, new.string);
+        self.update_len();
+    }
+
+    pub fn split(&mut self, at: usize) -> Self {
+        let beginning: String = self.string[..].graphemes(true).take(at).collect();
+        let remainder: String = self.string[..].graphemes(true).skip(at).collect();
+        self.string = beginning;
+        self.update_len();
+        Self::from(&remainder[..])
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.string.as_bytes()
+    }
+
+    fn update_len(&mut self) {
+        self.len = self.string[..].graphemes(true).count();
+    }
+
+    pub fn find(&self, query: &str, from: usize, direction: SearchDirection) -> Option<usize> {
+        if from > self.len || query.is_empty() {
+            return None;
+        }
+        match direction {
+            SearchDirection::Forward => {
+                let start = self.byte_index_of(from);
+                self.string[start..]
+                    .find(query)
+                    .map(|byte_idx| self.string[..start + byte_idx].graphemes(true).count())
+            }
+            SearchDirection::Backward => {
+                let end = self.byte_index_of(from);
+                self.string[..end]
+                    .rfind(query)
+                    .map(|byte_idx| self.string[..byte_idx].graphemes(true).count())
+            }
+        }
+    }
+
+    fn byte_index_of(&self, grapheme_index: usize) -> usize {
+        self.string[..]
+            .grapheme_indices(true)
+            .nth(grapheme_index)
+            .map_or(self.string.len(), |(byte_idx, _)| byte_idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_forward_advances_past_previous_match() {
+        let row = Row::from("foo bar foo foo");
+        let first = row.find("foo", 0, SearchDirection::Forward).unwrap();
+        assert_eq!(first, 0);
+        let second = row
+            .find("foo", first + 1, SearchDirection::Forward)
+            .unwrap();
+        assert_eq!(second, 8);
+    }
+
+    #[test]
+    fn find_backward_excludes_current_position() {
+        let row = Row::from("foo bar foo foo");
+        let last = row
+            .find("foo", row.len(), SearchDirection::Backward)
+            .unwrap();
+        assert_eq!(last, 12);
+        let prev = row.find("foo", last, SearchDirection::Backward).unwrap();
+        assert_eq!(prev, 8);
+    }
+
+    fn highlighted(text: &str) -> Vec<HighlightType> {
+        let mut row = Row::from(text);
+        row.highlight(None);
+        row.highlighting.clone()
+    }
+
+    #[test]
+    fn highlight_classifies_numbers() {
+        let types = highlighted("12.5");
+        assert_eq!(
+            types,
+            vec![
+                HighlightType::Number,
+                HighlightType::Number,
+                HighlightType::Number,
+                HighlightType::Number,
+            ]
+        );
+    }
+
+    #[test]
+    fn highlight_classifies_keywords_and_identifiers() {
+        let types = highlighted("let foo");
+        assert_eq!(
+            types,
+            vec![
+                HighlightType::Keyword,
+                HighlightType::Keyword,
+                HighlightType::Keyword,
+                HighlightType::Normal,
+                HighlightType::Normal,
+                HighlightType::Normal,
+                HighlightType::Normal,
+            ]
+        );
+    }
+
+    #[test]
+    fn highlight_keeps_keyword_prefix_as_identifier() {
+        // "letter" starts with the keyword "let" but is a distinct identifier,
+        // so the whole word must be highlighted as Normal, not just Keyword.
+        let types = highlighted("letter");
+        assert!(types.iter().all(|t| *t == HighlightType::Normal));
+    }
+
+    #[test]
+    fn highlight_classifies_quoted_strings() {
+        let types = highlighted("\"hi\"");
+        assert!(types.iter().all(|t| *t == HighlightType::String));
+    }
+
+    #[test]
+    fn highlight_stops_string_at_closing_quote() {
+        let types = highlighted("\"hi\" 1");
+        assert_eq!(types[0], HighlightType::String);
+        assert_eq!(types[3], HighlightType::String);
+        assert_eq!(types[5], HighlightType::Number);
+    }
+
+    #[test]
+    fn highlight_treats_rest_of_line_as_comment() {
+        let types = highlighted("1 // 2");
+        assert_eq!(types[0], HighlightType::Number);
+        assert!(types[2..].iter().all(|t| *t == HighlightType::Comment));
+    }
+
+    #[test]
+    fn highlight_does_not_treat_comment_marker_inside_string_as_comment() {
+        let types = highlighted("\"//\"");
+        assert!(types.iter().all(|t| *t == HighlightType::String));
+    }
+
+    #[test]
+    fn highlight_marks_search_matches_over_other_highlighting() {
+        let mut row = Row::from("let foo");
+        row.highlight(Some("foo"));
+        assert_eq!(
+            row.highlighting[4..],
+            vec![HighlightType::Match, HighlightType::Match, HighlightType::Match]
+        );
+    }
+}
@@ -1,5 +1,10 @@
-use std::io::{self, stdout, Write};
-use termion::{self, event::Key, input::TermRead};
+use std::io::{self, stdout, BufWriter, Stdout, Write};
+
+use crossterm::cursor;
+use crossterm::event::{self, Event as CEvent, KeyCode, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::queue;
 
 pub use crate::editor::Position;
 
@@ -8,18 +13,53 @@ pub struct Size {
     pub height: u16,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Ctrl(char),
+    Backspace,
+    Esc,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+}
+
+struct RawMode;
+
+impl RawMode {
+    fn new() -> Result<Self, io::Error> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        terminal::disable_raw_mode().expect("Failed to disable raw mode");
+    }
+}
+
 pub struct Terminal {
     size: Size,
+    stdout: BufWriter<Stdout>,
+    _raw_mode: RawMode,
 }
 
 impl Terminal {
     pub fn default() -> Result<Self, std::io::Error> {
-        let size = termion::terminal_size()?;
+        let size = terminal::size()?;
         Ok(Self {
             size: Size {
                 width: size.0,
                 height: size.1,
             },
+            stdout: BufWriter::new(stdout()),
+            _raw_mode: RawMode::new()?,
         })
     }
 
@@ -27,38 +67,85 @@ impl Terminal {
         &self.size
     }
 
-    pub fn clear_screen() {
-        print!("{}", termion::clear::All);
+    pub fn clear_screen(&mut self) {
+        queue!(self.stdout, Clear(ClearType::All)).unwrap();
     }
 
     #[allow(clippy::cast_possible_truncation)]
-    pub fn cursor_position(posi: &Position) {
-        let x = posi.x.saturating_add(1);
-        let y = posi.y.saturating_add(1);
-        print!("{}", termion::cursor::Goto(x as u16, y as u16));
+    pub fn cursor_position(&mut self, posi: &Position) {
+        queue!(self.stdout, cursor::MoveTo(posi.x as u16, posi.y as u16)).unwrap();
     }
 
-    pub fn flush() -> Result<(), io::Error> {
-        io::stdout().flush()
+    pub fn flush(&mut self) -> Result<(), io::Error> {
+        self.stdout.flush()
     }
 
+    /// Blocks the calling thread until a key is available. Doesn't need
+    /// instance state, so it's called from the dedicated reader thread rather
+    /// than through a `Terminal` the async loop also uses for drawing.
     pub fn read_key() -> Result<Key, std::io::Error> {
         loop {
-            if let Some(key) = io::stdin().lock().keys().next() {
-                return key;
+            if let CEvent::Key(key_event) = event::read()? {
+                if let Some(key) = map_key(key_event) {
+                    return Ok(key);
+                }
             }
         }
     }
 
-    pub fn cursor_show() {
-        print!("{}", termion::cursor::Show);
+    pub fn cursor_show(&mut self) {
+        queue!(self.stdout, cursor::Show).unwrap();
     }
 
-    pub fn cursor_hide() {
-        print!("{}", termion::cursor::Hide);
+    pub fn cursor_hide(&mut self) {
+        queue!(self.stdout, cursor::Hide).unwrap();
     }
 
-    pub fn clear_current_line() {
-        print!("{}", termion::clear::CurrentLine);
+    pub fn clear_current_line(&mut self) {
+        queue!(self.stdout, Clear(ClearType::CurrentLine)).unwrap();
+    }
+
+    pub fn set_bg_color(&mut self, color: Color) {
+        queue!(self.stdout, SetBackgroundColor(color)).unwrap();
+    }
+
+    pub fn reset_bg_color(&mut self) {
+        queue!(self.stdout, ResetColor).unwrap();
+    }
+
+    pub fn set_fg_color(&mut self, color: Color) {
+        queue!(self.stdout, SetForegroundColor(color)).unwrap();
+    }
+
+    pub fn reset_fg_color(&mut self) {
+        queue!(self.stdout, ResetColor).unwrap();
+    }
+
+    pub fn print(&mut self, text: &str) {
+        queue!(self.stdout, Print(text)).unwrap();
+    }
+
+    pub fn print_line(&mut self, text: &str) {
+        queue!(self.stdout, Print(text), Print("\r\n")).unwrap();
+    }
+}
+
+fn map_key(event: crossterm::event::KeyEvent) -> Option<Key> {
+    match event.code {
+        KeyCode::Char(c) if event.modifiers.contains(KeyModifiers::CONTROL) => Some(Key::Ctrl(c)),
+        KeyCode::Char(c) => Some(Key::Char(c)),
+        KeyCode::Enter => Some(Key::Char('\n')),
+        KeyCode::Tab => Some(Key::Char('\t')),
+        KeyCode::Backspace => Some(Key::Backspace),
+        KeyCode::Esc => Some(Key::Esc),
+        KeyCode::Up => Some(Key::Up),
+        KeyCode::Down => Some(Key::Down),
+        KeyCode::Left => Some(Key::Left),
+        KeyCode::Right => Some(Key::Right),
+        KeyCode::PageUp => Some(Key::PageUp),
+        KeyCode::PageDown => Some(Key::PageDown),
+        KeyCode::Home => Some(Key::Home),
+        KeyCode::End => Some(Key::End),
+        _ => None,
     }
 }